@@ -33,6 +33,7 @@ where
     index: Box<[u8]>,
     entries: Box<[E]>,
     stats: Stats,
+    generation: u32,
     n: PhantomData<N>,
 }
 
@@ -40,6 +41,27 @@ pub trait Entry {
     fn hash(&self) -> u64;
     fn valid(&self) -> bool;
     fn better_than(&self, rhs: &Self) -> bool;
+
+    /// The table generation this entry was stored in. Callers are expected
+    /// to stamp this with the owning table's current `generation()` when
+    /// building an entry to store.
+    fn generation(&self) -> u32;
+}
+
+/// Is `other` a more evictable table slot than `worst`, the least valuable
+/// slot found so far? An entry from an older search (generation) is always
+/// more evictable than one from the current search, no matter how deep it
+/// is; `Entry::better_than` only breaks ties within the same generation.
+/// This keeps a long-finished previous search's entries from crowding out
+/// fresh ones, which matters when a table is persisted and reused across
+/// moves via `dump`/`from_file`.
+fn more_evictable<E: Entry>(worst: &E, other: &E, current_generation: u32) -> bool {
+    let worst_stale = worst.generation() != current_generation;
+    let other_stale = other.generation() != current_generation;
+    if worst_stale != other_stale {
+        return other_stale;
+    }
+    worst.better_than(other)
 }
 
 pub const DEFAULT_TABLE_SIZE: usize = 1 << 30;
@@ -97,10 +119,23 @@ where
             index: new_default_slice(len),
             entries: new_default_slice(len),
             stats: Default::default(),
+            generation: 0,
             n: PhantomData,
         }
     }
 
+    /// Start a new search "generation". Call this once per root search
+    /// (not once per iterative-deepening ply) so that entries left over
+    /// from the previous search become preferred eviction targets instead
+    /// of competing with fresh ones forever.
+    pub fn advance_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
     pub fn lookup(&mut self, h: u64) -> Option<E> {
         self.stats.lookups += 1;
         let base = h as usize;
@@ -127,7 +162,7 @@ where
                 worst = Some(i);
                 break;
             } else if let Some(w) = worst {
-                if self.entries[w].better_than(&self.entries[i]) {
+                if more_evictable(&self.entries[w], &self.entries[i], self.generation) {
                     worst = Some(i);
                 }
             } else {
@@ -135,7 +170,10 @@ where
             }
         }
         let idx = worst.unwrap();
-        if !self.entries[idx].valid() || ent.better_than(&self.entries[idx]) {
+        if !self.entries[idx].valid()
+            || self.entries[idx].generation() != self.generation
+            || ent.better_than(&self.entries[idx])
+        {
             self.index[idx] = (ent.hash() & 0xff) as u8;
             self.entries[idx] = ent.clone();
             self.stats.stores += 1;
@@ -211,6 +249,7 @@ where
 
     handles: AtomicUsize,
     stats: Mutex<Stats>,
+    generation: AtomicU32,
 
     n: PhantomData<N>,
 }
@@ -252,9 +291,19 @@ where
             n: PhantomData,
             handles: AtomicUsize::new(0),
             stats: Default::default(),
+            generation: AtomicU32::new(0),
         }
     }
 
+    /// Start a new search "generation"; see `TranspositionTable::advance_generation`.
+    pub fn advance_generation(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
     pub fn from_reader(r: &mut dyn io::Read) -> io::Result<Self> {
         TranspositionTable::from_reader(r).map(|t| Self::from_table(t))
     }
@@ -274,6 +323,7 @@ where
             n: PhantomData,
             handles: AtomicUsize::new(0),
             stats: Default::default(),
+            generation: AtomicU32::new(t.generation),
         }
     }
 
@@ -318,6 +368,7 @@ where
     pub fn store(&self, stats: &mut Stats, ent: &E) -> bool {
         let _lk = self.write.lock();
         debug_assert!(ent.valid());
+        let current_generation = self.generation();
         let mut worst: Option<usize> = None;
         let base = ent.hash() as usize;
         for j in 0..N::to_usize() {
@@ -327,7 +378,8 @@ where
                 worst = Some(i);
                 break;
             } else if let Some(w) = worst {
-                if (unsafe { self.entries[w].get().as_ref().unwrap() }).better_than(&ei) {
+                let ew = unsafe { self.entries[w].get().as_ref().unwrap() };
+                if more_evictable(ew, ei, current_generation) {
                     worst = Some(i);
                 }
             } else {
@@ -337,7 +389,7 @@ where
         let idx = worst.unwrap();
         let dst = unsafe { self.entries[idx].get().as_mut().unwrap() };
 
-        if !dst.valid() || ent.better_than(&dst) {
+        if !dst.valid() || dst.generation() != current_generation || ent.better_than(&dst) {
             let seq = &self.counters[idx % self.counters.len()];
             seq.fetch_add(1, Ordering::Relaxed);
             fence(Ordering::Release);
@@ -398,6 +450,14 @@ where
         self.table.lookup(&mut self.stats, h)
     }
 
+    pub fn advance_generation(&self) {
+        self.table.advance_generation();
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.table.generation()
+    }
+
     pub fn store(&mut self, e: &E) -> bool {
         self.table.store(&mut self.stats, e)
     }
@@ -405,6 +465,12 @@ where
     pub fn dump(&self, w: &mut dyn io::Write) -> io::Result<()> {
         self.table.dump(w)
     }
+
+    /// This handle's own running stats, not merged with other handles'
+    /// (that merge only happens in the table's `stats` on `Drop`).
+    pub fn stats(&self) -> Stats {
+        self.stats.clone()
+    }
 }
 
 impl<'a, E, N> Drop for ConcurrentTranspositionTableHandle<'a, E, N>
@@ -428,3 +494,58 @@ where
         self.table.handle()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct TestEntry {
+        depth: i32,
+        generation: u32,
+    }
+
+    impl Entry for TestEntry {
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn valid(&self) -> bool {
+            true
+        }
+
+        fn better_than(&self, rhs: &Self) -> bool {
+            self.depth >= rhs.depth
+        }
+
+        fn generation(&self) -> u32 {
+            self.generation
+        }
+    }
+
+    fn entry(depth: i32, generation: u32) -> TestEntry {
+        TestEntry { depth, generation }
+    }
+
+    #[test]
+    fn stale_entry_displaces_fresh_worst() {
+        let worst = entry(1, 2); // current generation, shallow
+        let other = entry(10, 1); // stale, deep
+        assert!(more_evictable(&worst, &other, 2));
+    }
+
+    #[test]
+    fn fresh_entry_never_displaces_stale_worst() {
+        let worst = entry(10, 1); // stale, deep
+        let other = entry(1, 2); // current generation, shallow
+        assert!(!more_evictable(&worst, &other, 2));
+    }
+
+    #[test]
+    fn same_generation_falls_back_to_better_than() {
+        let worst = entry(10, 2); // deeper, so "better"
+        let other = entry(1, 2);
+        assert!(more_evictable(&worst, &other, 2));
+        assert!(!more_evictable(&other, &worst, 2));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::game;
+use crate::minimax::AI;
+
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+const UCT_C: f64 = std::f64::consts::SQRT_2;
+
+/// One node of the search tree. Children are a contiguous range
+/// `[children_start, children_start + children_len)` into `MCTS::arena`
+/// rather than `Box`/`Rc` pointers, so growing the tree is a single `Vec`
+/// push and nodes stay cache-local during selection.
+struct Node {
+    parent: u32,
+    m: game::Move,
+    n: u32,
+    w: f64,
+    children_start: u32,
+    children_len: u16,
+}
+
+/// Monte-Carlo Tree Search: an anytime alternative to `Minimax` for
+/// positions where the static evaluation heuristic is weak. Budgeting
+/// mirrors `Minimax`'s `with_depth`/`with_timeout` style, except
+/// `max_depth` here caps the number of playouts rather than the search
+/// depth, since MCTS has no fixed depth to cut off at.
+pub struct MCTS {
+    rng: rand::rngs::ThreadRng,
+    max_depth: Option<i32>,
+    timeout: Option<Duration>,
+    arena: Vec<Node>,
+}
+
+impl MCTS {
+    #[allow(dead_code)]
+    pub fn with_depth(iterations: i32) -> Self {
+        Self {
+            rng: rand::thread_rng(),
+            max_depth: Some(iterations),
+            timeout: None,
+            arena: Vec::new(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            rng: rand::thread_rng(),
+            max_depth: None,
+            timeout: Some(timeout),
+            arena: Vec::new(),
+        }
+    }
+
+    fn select_child(&self, children_start: u32, children_len: u16, parent_n: u32) -> u32 {
+        let mut best_idx = children_start;
+        let mut best_score = f64::NEG_INFINITY;
+        let log_parent_n = (parent_n as f64).ln();
+        for i in 0..children_len {
+            let idx = children_start + i as u32;
+            let child = &self.arena[idx as usize];
+            let score = if child.n == 0 {
+                f64::INFINITY
+            } else {
+                (child.w / child.n as f64) + UCT_C * (log_parent_n / child.n as f64).sqrt()
+            };
+            if score > best_score {
+                best_score = score;
+                best_idx = idx;
+            }
+        }
+        best_idx
+    }
+
+    fn expand(&mut self, idx: u32, g: &game::Game) {
+        let start = self.arena.len() as u32;
+        let mut count: u16 = 0;
+        for m in g.all_moves() {
+            self.arena.push(Node {
+                parent: idx,
+                m,
+                n: 0,
+                w: 0.0,
+                children_start: 0,
+                children_len: 0,
+            });
+            count += 1;
+        }
+        let node = &mut self.arena[idx as usize];
+        node.children_start = start;
+        node.children_len = count;
+    }
+
+    // Descend from the root, picking the UCT-best child at each step
+    // (treating an unvisited child as having infinite priority) and
+    // expanding any fully-searched leaf it passes through. Stops at the
+    // first unvisited node it reaches, or at a terminal position.
+    fn select_and_expand(&mut self, root: &game::Game) -> (u32, game::Game) {
+        let mut idx: u32 = 0;
+        let mut g = root.clone();
+        loop {
+            if g.game_state() != game::BoardState::InPlay {
+                return (idx, g);
+            }
+            if self.arena[idx as usize].children_len == 0 {
+                self.expand(idx, &g);
+            }
+            let node = &self.arena[idx as usize];
+            if node.children_len == 0 {
+                // No legal moves but still "in play" shouldn't happen; bail
+                // out to the current node rather than looping forever.
+                return (idx, g);
+            }
+            let child_idx = self.select_child(node.children_start, node.children_len, node.n);
+            let m = self.arena[child_idx as usize].m;
+            g = g.make_move(m).unwrap();
+            let visited = self.arena[child_idx as usize].n > 0;
+            idx = child_idx;
+            if !visited {
+                return (idx, g);
+            }
+        }
+    }
+
+    fn rollout(&mut self, g: &game::Game) -> game::BoardState {
+        let mut g = g.clone();
+        loop {
+            match g.game_state() {
+                game::BoardState::InPlay => {
+                    let moves: Vec<game::Move> = g.all_moves().collect();
+                    let pick = self.rng.gen_range(0..moves.len());
+                    g = g.make_move(moves[pick]).unwrap();
+                }
+                state => return state,
+            }
+        }
+    }
+
+    fn backprop(&mut self, leaf: u32, value: f64) {
+        let mut idx = leaf;
+        let mut value = value;
+        loop {
+            let node = &mut self.arena[idx as usize];
+            node.n += 1;
+            node.w += value;
+            if idx == 0 {
+                break;
+            }
+            idx = node.parent;
+            value = -value;
+        }
+    }
+
+    fn iterate(&mut self, root: &game::Game) {
+        let (idx, g) = self.select_and_expand(root);
+        let leaf_player = g.player();
+        let result = match g.game_state() {
+            game::BoardState::InPlay => self.rollout(&g),
+            terminal => terminal,
+        };
+        let value = match result {
+            game::BoardState::Won(p) if p == leaf_player => 1.0,
+            game::BoardState::Won(_) => -1.0,
+            game::BoardState::Drawn => 0.5,
+            game::BoardState::InPlay => unreachable!(),
+        };
+        // `value` is from the view of the player to move at the leaf, but a
+        // node's `w` is read by its parent's UCT, i.e. from the view of the
+        // player who made the node's move.
+        self.backprop(idx, -value);
+    }
+}
+
+impl AI for MCTS {
+    fn select_move(&mut self, g: &game::Game) -> game::Move {
+        self.arena.clear();
+        self.arena.push(Node {
+            parent: u32::MAX,
+            m: game::Move::none(),
+            n: 0,
+            w: 0.0,
+            children_start: 0,
+            children_len: 0,
+        });
+
+        let deadline: Option<Instant> = self.timeout.map(|t| Instant::now() + t);
+        let mut iterations = 0;
+        loop {
+            self.iterate(g);
+            iterations += 1;
+            if self.max_depth.map(|d| iterations >= d).unwrap_or(false) {
+                break;
+            }
+            if deadline.map(|d| Instant::now() > d).unwrap_or(false) {
+                break;
+            }
+        }
+
+        let root = &self.arena[0];
+        let mut best_idx = root.children_start;
+        let mut best_n: u32 = 0;
+        for i in 0..root.children_len {
+            let idx = root.children_start + i as u32;
+            if self.arena[idx as usize].n >= best_n {
+                best_n = self.arena[idx as usize].n;
+                best_idx = idx;
+            }
+        }
+        self.arena[best_idx as usize].m
+    }
+}
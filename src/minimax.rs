@@ -1,19 +1,157 @@
 extern crate test;
 use crate::game;
+use crate::lib::table;
 
 use rand;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::time::{Duration, Instant};
+use typenum::U4;
 
 pub trait AI {
     fn select_move(&mut self, g: &game::Game) -> game::Move;
 }
 
+/// Whether a stored search value is the exact minimax value of a node, or
+/// only a bound on it because the search was cut off by alpha/beta.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Bound {
+    Exact,
+    LowerBound,
+    UpperBound,
+}
+
+/// A transposition-table entry for the negamax search below.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Entry {
+    pub(crate) hash: u64,
+    pub(crate) depth: i32,
+    pub(crate) value: i64,
+    pub(crate) bound: Bound,
+    pub(crate) best_move: game::Move,
+    pub(crate) valid: bool,
+    pub(crate) generation: u32,
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            hash: 0,
+            depth: 0,
+            value: 0,
+            bound: Bound::Exact,
+            best_move: game::Move::none(),
+            valid: false,
+            generation: 0,
+        }
+    }
+}
+
+impl table::Entry for Entry {
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn valid(&self) -> bool {
+        self.valid
+    }
+
+    fn better_than(&self, rhs: &Self) -> bool {
+        self.depth >= rhs.depth
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+pub(crate) fn hash_position(g: &game::Game) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    g.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Whether a negamax value searched with window (alpha_orig, beta) is exact
+// or only a bound, given alpha/beta cutoff behavior: a value that never
+// rose above alpha_orig means every move failed low (UpperBound), a value
+// that reached beta means the loop broke on a cutoff (LowerBound).
+fn classify_bound(value: i64, alpha_orig: i64, beta: i64) -> Bound {
+    if value <= alpha_orig {
+        Bound::UpperBound
+    } else if value >= beta {
+        Bound::LowerBound
+    } else {
+        Bound::Exact
+    }
+}
+
+fn move_index(m: game::Move) -> usize {
+    m.board() * 9 + m.cell()
+}
+
+/// Deepest ply the move orderer tracks killer moves for; plies beyond this
+/// just skip the killer check and fall back to the history heuristic.
+const MAX_KILLER_PLY: usize = 64;
+
+/// Orders candidate moves at a node before they're searched, cheaply
+/// approximating "search the move most likely to cause a cutoff first"
+/// without doing any actual search:
+///
+///   1. the best move from the transposition entry for this position, if any
+///   2. up to two "killer" moves per ply: quiet moves that caused a
+///      beta-cutoff the last time this ply was searched
+///   3. a history score: how often a move has caused a cutoff anywhere in
+///      the tree, weighted by the depth of the cutoff
+///
+/// State persists across the whole iterative-deepening loop in `Minimax`,
+/// so later, deeper iterations benefit from ordering learned by earlier,
+/// shallower ones.
+struct MoveOrderer {
+    killers: [[game::Move; 2]; MAX_KILLER_PLY],
+    history: [u32; 81],
+}
+
+impl MoveOrderer {
+    fn new() -> Self {
+        MoveOrderer {
+            killers: [[game::Move::none(); 2]; MAX_KILLER_PLY],
+            history: [0; 81],
+        }
+    }
+
+    fn order(&self, moves: &mut Vec<game::Move>, tt_move: game::Move, ply: usize) {
+        let killers = self.killers.get(ply).copied().unwrap_or([game::Move::none(); 2]);
+        moves.sort_by_key(|&m| {
+            let score: i64 = if m == tt_move {
+                i64::MAX
+            } else if m == killers[0] || m == killers[1] {
+                i64::MAX - 1
+            } else {
+                self.history[move_index(m)] as i64
+            };
+            -score
+        });
+    }
+
+    fn record_cutoff(&mut self, m: game::Move, depth: i32, ply: usize) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != m {
+                killers[1] = killers[0];
+                killers[0] = m;
+            }
+        }
+        self.history[move_index(m)] += (depth * depth) as u32;
+    }
+}
+
 pub struct Minimax {
     #[allow(dead_code)]
     rng: rand::rngs::ThreadRng,
 
     max_depth: Option<i32>,
     timeout: Option<Duration>,
+    table: table::TranspositionTable<Entry, U4>,
+    orderer: MoveOrderer,
 }
 
 const EVAL_WON: i64 = 1 << 60;
@@ -23,6 +161,8 @@ const EVAL_PARTIAL_TWO: i64 = 3;
 
 const OVERALL_PARTIAL_WIN: i64 = 10;
 
+const ASPIRATION_DELTA: i64 = 4 * OVERALL_PARTIAL_WIN;
+
 impl Minimax {
     #[allow(dead_code)]
     pub fn with_depth(depth: i32) -> Self {
@@ -30,6 +170,8 @@ impl Minimax {
             rng: rand::thread_rng(),
             max_depth: Some(depth),
             timeout: None,
+            table: table::TranspositionTable::new(),
+            orderer: MoveOrderer::new(),
         }
     }
 
@@ -39,6 +181,8 @@ impl Minimax {
             rng: rand::thread_rng(),
             max_depth: None,
             timeout: Some(timeout),
+            table: table::TranspositionTable::new(),
+            orderer: MoveOrderer::new(),
         }
     }
 
@@ -122,34 +266,130 @@ impl Minimax {
         }
     }
 
-    fn minimax(&mut self, g: &game::Game, depth: i32) -> (game::Move, i64) {
-        if depth <= 0 {
+    fn minimax(
+        &mut self,
+        g: &game::Game,
+        depth: i32,
+        mut alpha: i64,
+        mut beta: i64,
+        ply: usize,
+    ) -> (game::Move, i64) {
+        if depth <= 0 || g.game_state() != game::BoardState::InPlay {
             return (game::Move::none(), self.evaluate(g));
         }
 
+        let alpha_orig = alpha;
+        let hash = hash_position(g);
+        let mut tt_move = game::Move::none();
+        if let Some(entry) = self.table.lookup(hash) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return (entry.best_move, entry.value),
+                    Bound::LowerBound => alpha = alpha.max(entry.value),
+                    Bound::UpperBound => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return (entry.best_move, entry.value);
+                }
+            }
+        }
+
+        let mut moves: Vec<game::Move> = g.all_moves().collect();
+        self.orderer.order(&mut moves, tt_move, ply);
+
+        // Principal-variation search: trust the move ordering enough to
+        // search the first (best-guess) move with the full window, then
+        // search the rest with a null window that only asks "is this move
+        // better than alpha?". A null-window result landing inside
+        // (alpha, beta) means the ordering guessed wrong and that move is
+        // actually part of the principal variation, so it gets a full
+        // re-search.
         let mut best = (game::Move::none(), EVAL_LOST - 1);
-        let moves = g.all_moves();
+        let mut first = true;
         for m in moves {
             let child = g.make_move(m).unwrap();
-            let mut eval = self.minimax(&child, depth - 1);
-            eval.1 *= -1;
-            if eval.1 > best.1 {
-                best = (m, eval.1)
+            let score = if first {
+                -self.minimax(&child, depth - 1, -beta, -alpha, ply + 1).1
+            } else {
+                let mut s = -self.minimax(&child, depth - 1, -alpha - 1, -alpha, ply + 1).1;
+                if s > alpha && s < beta {
+                    s = -self.minimax(&child, depth - 1, -beta, -alpha, ply + 1).1;
+                }
+                s
+            };
+            first = false;
+            if score > best.1 {
+                best = (m, score)
+            }
+            if best.1 > alpha {
+                alpha = best.1;
+            }
+            if alpha >= beta {
+                self.orderer.record_cutoff(m, depth, ply);
+                break;
             }
         }
+
+        let bound = classify_bound(best.1, alpha_orig, beta);
+        self.table.store(&Entry {
+            hash,
+            depth,
+            value: best.1,
+            bound,
+            best_move: best.0,
+            valid: true,
+            generation: self.table.generation(),
+        });
+
         best
     }
+
+    // Search `depth` using the previous iteration's score as an aspiration
+    // window instead of the full (-inf, +inf) range. A fail-low or
+    // fail-high result only widens the bound that actually failed, doubling
+    // it outward each retry; both bounds saturate at the true full window
+    // so mate scores (EVAL_WON/EVAL_LOST) can't trigger endless re-searches.
+    fn aspiration_search(&mut self, g: &game::Game, depth: i32, prev_score: Option<i64>) -> (game::Move, i64) {
+        let full_alpha = EVAL_LOST - 1;
+        let full_beta = EVAL_WON + 1;
+        let center = match prev_score {
+            Some(v) => v,
+            None => return self.minimax(g, depth, full_alpha, full_beta, 0),
+        };
+
+        let mut delta = ASPIRATION_DELTA;
+        let mut alpha = center.saturating_sub(delta).max(full_alpha);
+        let mut beta = center.saturating_add(delta).min(full_beta);
+        loop {
+            let got = self.minimax(g, depth, alpha, beta, 0);
+            if got.1 <= alpha && alpha > full_alpha {
+                delta = delta.saturating_mul(2);
+                alpha = center.saturating_sub(delta).max(full_alpha);
+                continue;
+            }
+            if got.1 >= beta && beta < full_beta {
+                delta = delta.saturating_mul(2);
+                beta = center.saturating_add(delta).min(full_beta);
+                continue;
+            }
+            return got;
+        }
+    }
 }
 
 impl AI for Minimax {
     fn select_move(&mut self, g: &game::Game) -> game::Move {
+        self.table.advance_generation();
         let deadline: Option<Instant> = self.timeout.map(|t| Instant::now() + t);
         let mut depth = 0;
         let mut result: game::Move;
+        let mut prev_score: Option<i64> = None;
         loop {
             depth += 1;
             let t_before = Instant::now();
-            let got = self.minimax(g, depth);
+            let got = self.aspiration_search(g, depth, prev_score);
+            prev_score = Some(got.1);
             let ply_duration = Instant::now().duration_since(t_before);
             result = got.0;
             println!(
@@ -183,4 +423,19 @@ mod tests {
         let ai = Minimax::with_depth(3);
         b.iter(|| ai.evaluate(black_box(&g)));
     }
+
+    #[test]
+    fn classify_bound_exact_inside_window() {
+        assert_eq!(classify_bound(5, 0, 10), Bound::Exact);
+    }
+
+    #[test]
+    fn classify_bound_upper_when_failed_low() {
+        assert_eq!(classify_bound(0, 0, 10), Bound::UpperBound);
+    }
+
+    #[test]
+    fn classify_bound_lower_when_cutoff() {
+        assert_eq!(classify_bound(10, 0, 10), Bound::LowerBound);
+    }
 }
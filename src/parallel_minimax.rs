@@ -0,0 +1,259 @@
+use crate::game;
+use crate::lib::table::{ConcurrentTranspositionTable, ConcurrentTranspositionTableHandle, Stats};
+use crate::minimax::{hash_position, Bound, Entry, AI};
+
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use typenum::U4;
+
+const EVAL_WON: i64 = 1 << 60;
+const EVAL_LOST: i64 = -(1 << 60);
+
+/// Worker-local search state: a handle onto the shared transposition table
+/// plus a plain alpha-beta negamax that runs against a
+/// `ConcurrentTranspositionTableHandle` rather than the single-threaded
+/// `TranspositionTable`. Unlike `Minimax::minimax` it does no
+/// principal-variation search and no killer/history ordering; only the
+/// transposition entry's best move is tried first.
+struct WorkerSearch<'a> {
+    handle: ConcurrentTranspositionTableHandle<'a, Entry, U4>,
+}
+
+impl<'a> WorkerSearch<'a> {
+    fn negamax(&mut self, g: &game::Game, depth: i32, mut alpha: i64, mut beta: i64) -> i64 {
+        if depth <= 0 || g.game_state() != game::BoardState::InPlay {
+            return evaluate(g);
+        }
+
+        let alpha_orig = alpha;
+        let hash = hash_position(g);
+        let mut tt_move = game::Move::none();
+        if let Some(entry) = self.handle.lookup(hash) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.value,
+                    Bound::LowerBound => alpha = alpha.max(entry.value),
+                    Bound::UpperBound => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return entry.value;
+                }
+            }
+        }
+
+        let mut moves: Vec<game::Move> = g.all_moves().collect();
+        if let Some(pos) = moves.iter().position(|&m| m == tt_move) {
+            moves.swap(0, pos);
+        }
+
+        let mut best = (game::Move::none(), EVAL_LOST - 1);
+        for m in moves {
+            let child = g.make_move(m).unwrap();
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha);
+            if score > best.1 {
+                best = (m, score);
+            }
+            if best.1 > alpha {
+                alpha = best.1;
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best.1 <= alpha_orig {
+            Bound::UpperBound
+        } else if best.1 >= beta {
+            Bound::LowerBound
+        } else {
+            Bound::Exact
+        };
+        self.handle.store(&Entry {
+            hash,
+            depth,
+            value: best.1,
+            bound,
+            best_move: best.0,
+            valid: true,
+            generation: self.handle.generation(),
+        });
+
+        best.1
+    }
+}
+
+// Scores only terminal positions: a win for the player to move is EVAL_WON,
+// a loss is EVAL_LOST, and every other position, drawn or still in play,
+// scores 0. The partial-line heuristic of `Minimax::evaluate` is not
+// applied here, and `Minimax` does not use this function.
+fn evaluate(g: &game::Game) -> i64 {
+    match g.game_state() {
+        game::BoardState::Won(p) => {
+            if p == g.player() {
+                EVAL_WON
+            } else {
+                EVAL_LOST
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// A root move handed out to workers through the work-stealing pool.
+struct RootTask {
+    m: game::Move,
+}
+
+// Retry `Steal::Retry` the same way `steal_batch_and_pop` is retried below:
+// a peer racing its own pop can make `steal()` spuriously report `Retry`
+// even though its queue isn't empty, so treating `Retry` as "no work" would
+// let a worker give up while a peer still has moves left to search.
+fn steal_from_peers(stealers: &[Stealer<RootTask>]) -> Option<RootTask> {
+    loop {
+        let mut saw_retry = false;
+        for s in stealers {
+            match s.steal() {
+                Steal::Success(t) => return Some(t),
+                Steal::Retry => saw_retry = true,
+                Steal::Empty => (),
+            }
+        }
+        if !saw_retry {
+            return None;
+        }
+    }
+}
+
+/// Parallel alpha-beta search: the root's legal moves are split across
+/// worker threads, each searching its share of root subtrees to the same
+/// depth and sharing cutoffs through one `ConcurrentTranspositionTable`.
+/// Root moves are distributed through a `crossbeam-deque` injector so idle
+/// workers steal subtrees from busy ones instead of sitting still.
+pub struct ParallelMinimax {
+    threads: usize,
+    max_depth: Option<i32>,
+    timeout: Option<Duration>,
+    table: Arc<ConcurrentTranspositionTable<Entry, U4>>,
+}
+
+impl ParallelMinimax {
+    #[allow(dead_code)]
+    pub fn with_threads(threads: usize) -> Self {
+        Self {
+            threads,
+            max_depth: None,
+            timeout: None,
+            table: Arc::new(ConcurrentTranspositionTable::new()),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_depth(threads: usize, depth: i32) -> Self {
+        let mut ai = Self::with_threads(threads);
+        ai.max_depth = Some(depth);
+        ai
+    }
+
+    #[allow(dead_code)]
+    pub fn with_timeout(threads: usize, timeout: Duration) -> Self {
+        let mut ai = Self::with_threads(threads);
+        ai.timeout = Some(timeout);
+        ai
+    }
+
+    fn search_depth(&self, g: &game::Game, depth: i32) -> (game::Move, i64, Stats) {
+        let injector: Arc<Injector<RootTask>> = Arc::new(Injector::new());
+        for m in g.all_moves() {
+            injector.push(RootTask { m });
+        }
+
+        let locals: Vec<Worker<RootTask>> = (0..self.threads).map(|_| Worker::new_fifo()).collect();
+        let stealers: Arc<Vec<Stealer<RootTask>>> =
+            Arc::new(locals.iter().map(|w| w.stealer()).collect());
+
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| {
+            for local in locals.into_iter() {
+                let injector = Arc::clone(&injector);
+                let stealers = Arc::clone(&stealers);
+                let handle = self.table.handle();
+                let tx = tx.clone();
+                let g = g.clone();
+                scope.spawn(move || {
+                    // Every worker searches root moves to the same `depth`
+                    // so `search_depth` is comparing scores from equal-depth
+                    // searches; cooperation between workers comes entirely
+                    // from sharing cutoffs through the table handle.
+                    let mut searcher = WorkerSearch { handle };
+                    loop {
+                        let task = local.pop().or_else(|| loop {
+                            match injector.steal_batch_and_pop(&local) {
+                                Steal::Success(t) => return Some(t),
+                                Steal::Retry => continue,
+                                Steal::Empty => return steal_from_peers(&stealers),
+                            }
+                        });
+                        let task = match task {
+                            Some(t) => t,
+                            None => break,
+                        };
+                        let child = g.make_move(task.m).unwrap();
+                        let score = -searcher.negamax(&child, depth - 1, EVAL_LOST - 1, EVAL_WON + 1);
+                        let _ = tx.send((task.m, score));
+                        // `searcher.handle` is dropped with the thread closure
+                        // at the end of this scope, which merges its final
+                        // running totals into `self.table`'s stats exactly
+                        // once; see `ConcurrentTranspositionTableHandle`'s
+                        // `Drop` impl.
+                    }
+                });
+            }
+        });
+        drop(tx);
+
+        let mut best = (game::Move::none(), EVAL_LOST - 1);
+        for (m, score) in rx {
+            if score > best.1 {
+                best = (m, score);
+            }
+        }
+        (best.0, best.1, self.table.stats())
+    }
+}
+
+impl AI for ParallelMinimax {
+    fn select_move(&mut self, g: &game::Game) -> game::Move {
+        self.table.advance_generation();
+        let deadline: Option<Instant> = self.timeout.map(|t| Instant::now() + t);
+        let mut depth = 0;
+        let mut result = game::Move::none();
+        loop {
+            depth += 1;
+            let t_before = Instant::now();
+            let (m, v, stats) = self.search_depth(g, depth);
+            let ply_duration = Instant::now().duration_since(t_before);
+            result = m;
+            println!(
+                "parallel_minimax depth={} move={} v={} lookups={} hits={} t={}.{:03}s",
+                depth,
+                m,
+                v,
+                stats.lookups,
+                stats.hits,
+                ply_duration.as_secs(),
+                ply_duration.subsec_millis(),
+            );
+            if self.max_depth.map(|d| depth >= d).unwrap_or(false) {
+                break;
+            }
+            if deadline.map(|d| Instant::now() > d).unwrap_or(false) {
+                break;
+            }
+        }
+        result
+    }
+}